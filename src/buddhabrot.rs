@@ -0,0 +1,145 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use num_complex::Complex64;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::psychedelic_color;
+
+/// samples drawn per rayon batch; keeps per-thread RNG state reseeded often
+/// enough that work stealing doesn't starve any one batch
+const BATCH_SIZE: u64 = 4096;
+
+/// generates a Buddhabrot density image: orbits of escaping points are
+/// accumulated into a histogram, then normalized and colored
+///
+/// # Safety
+/// `buffer` must point to a writable buffer of at least `width * height * 4`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn generate_buddhabrot(
+    buffer: *mut u8,
+    width: i32,
+    height: i32,
+    center_x: f64,
+    center_y: f64,
+    zoom: f64,
+    max_iterations: i32,
+    samples: u64,
+    seed: u64,
+) {
+    let buffer_slice = unsafe {
+        std::slice::from_raw_parts_mut(buffer, (width * height * 4) as usize)
+    };
+
+    let density = accumulate(width, height, center_x, center_y, zoom, max_iterations, samples, seed);
+
+    // normalize by a log curve so the rare, high-count pixels near the
+    // origin don't wash out the faint outer structure
+    let max_count = density
+        .iter()
+        .map(|c| c.load(Ordering::Relaxed))
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+
+    buffer_slice
+        .par_chunks_exact_mut(4)
+        .enumerate()
+        .for_each(|(i, pixel)| {
+            let count = density[i].load(Ordering::Relaxed) as f64;
+            let t = count.ln_1p() / max_count.ln_1p();
+            let color = psychedelic_color(t);
+
+            pixel[0] = color.r;
+            pixel[1] = color.g;
+            pixel[2] = color.b;
+            pixel[3] = 255;
+        });
+}
+
+/// runs `samples` random orbits in parallel batches, accumulating escaping
+/// orbits into a per-pixel histogram
+#[allow(clippy::too_many_arguments)]
+fn accumulate(
+    width: i32,
+    height: i32,
+    center_x: f64,
+    center_y: f64,
+    zoom: f64,
+    max_iter: i32,
+    samples: u64,
+    seed: u64,
+) -> Vec<AtomicU32> {
+    let density: Vec<AtomicU32> = (0..(width as usize * height as usize))
+        .map(|_| AtomicU32::new(0))
+        .collect();
+
+    let num_batches = samples.div_ceil(BATCH_SIZE);
+
+    (0..num_batches).into_par_iter().for_each(|batch| {
+        // each batch gets its own reproducible RNG stream, derived from the
+        // caller's seed so results don't depend on how rayon schedules work
+        let mut rng = StdRng::seed_from_u64(seed.wrapping_add(batch));
+        let batch_samples = BATCH_SIZE.min(samples - batch * BATCH_SIZE);
+
+        for _ in 0..batch_samples {
+            let c = random_point_in_disk(&mut rng, 2.0);
+            if let Some(orbit) = escaping_orbit(c, max_iter) {
+                for z in orbit {
+                    if let Some(idx) = complex_to_pixel_index(z, width, height, center_x, center_y, zoom) {
+                        density[idx].fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+    });
+
+    density
+}
+
+/// uniformly samples a point within a disk of the given radius, by rejection
+/// sampling the bounding square
+fn random_point_in_disk(rng: &mut StdRng, radius: f64) -> Complex64 {
+    loop {
+        let re = rng.gen_range(-radius..radius);
+        let im = rng.gen_range(-radius..radius);
+        let c = Complex64::new(re, im);
+        if c.norm_sqr() <= radius * radius {
+            return c;
+        }
+    }
+}
+
+/// iterates z = z^2 + c and, if the orbit escapes before `max_iter`, returns
+/// every intermediate z_n visited along the way; orbits that never escape
+/// (i.e. c is in the set) are discarded entirely
+fn escaping_orbit(c: Complex64, max_iter: i32) -> Option<Vec<Complex64>> {
+    let mut z = Complex64::new(0.0, 0.0);
+    let mut orbit = Vec::with_capacity(max_iter as usize);
+
+    for _ in 0..max_iter {
+        z = z * z + c;
+        orbit.push(z);
+        if z.norm_sqr() > 4.0 {
+            return Some(orbit);
+        }
+    }
+
+    None
+}
+
+/// maps a point in the complex plane back to a pixel index, using the same
+/// center/zoom convention as `calculate_mandelbrot`'s forward transform
+fn complex_to_pixel_index(z: Complex64, width: i32, height: i32, center_x: f64, center_y: f64, zoom: f64) -> Option<usize> {
+    let scale = 4.0 / zoom;
+    let px = (z.re - center_x) * width as f64 / scale + width as f64 / 2.0;
+    let py = (z.im - center_y) * height as f64 / scale + height as f64 / 2.0;
+
+    if px < 0.0 || py < 0.0 || px >= width as f64 || py >= height as f64 {
+        return None;
+    }
+
+    Some(py as usize * width as usize + px as usize)
+}
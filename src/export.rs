@@ -0,0 +1,108 @@
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+
+use image::{ImageBuffer, Rgba};
+use rayon::prelude::*;
+
+use crate::{mandelbrot_escape_t, pixel_to_complex, psychedelic_color, Color};
+
+/// anything that can go wrong turning a rendered fractal into a file on disk
+#[derive(Debug)]
+pub enum ExportError {
+    Io(std::io::Error),
+    Encode(image::ImageError),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io(e) => write!(f, "failed to write fractal image: {e}"),
+            ExportError::Encode(e) => write!(f, "failed to encode fractal image: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<std::io::Error> for ExportError {
+    fn from(e: std::io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+impl From<image::ImageError> for ExportError {
+    fn from(e: image::ImageError) -> Self {
+        ExportError::Encode(e)
+    }
+}
+
+/// renders a Mandelbrot fractal straight to a PNG or PPM file, picking the
+/// format from `path`'s extension (`.ppm`/`.pnm` for binary PPM, anything
+/// else for PNG). This is the library/CLI entry point for batch-generating
+/// stills without a consumer having to manage the raw FFI buffer itself.
+pub fn render_fractal_to_file(
+    path: impl AsRef<Path>,
+    width: u32,
+    height: u32,
+    center_x: f64,
+    center_y: f64,
+    zoom: f64,
+    max_iterations: i32,
+) -> Result<(), ExportError> {
+    let path = path.as_ref();
+    let buffer = render_rgba(width as i32, height as i32, center_x, center_y, zoom, max_iterations);
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("ppm") || ext.eq_ignore_ascii_case("pnm") => {
+            write_ppm(path, width, height, &buffer)
+        }
+        _ => write_png(path, width, height, &buffer),
+    }
+}
+
+/// renders the same psychedelic-colored Mandelbrot as `generate_fractal`
+/// into an owned RGBA buffer
+fn render_rgba(width: i32, height: i32, center_x: f64, center_y: f64, zoom: f64, max_iter: i32) -> Vec<u8> {
+    let mut buffer = vec![0u8; (width * height * 4) as usize];
+
+    buffer
+        .par_chunks_exact_mut(4)
+        .enumerate()
+        .for_each(|(i, pixel)| {
+            let x = i as i32 % width;
+            let y = i as i32 / width;
+
+            let c = pixel_to_complex(x, y, width, height, center_x, center_y, zoom);
+            let color = match mandelbrot_escape_t(c, max_iter) {
+                Some(t) => psychedelic_color(t),
+                None => Color { r: 0, g: 0, b: 0 },
+            };
+
+            pixel[0] = color.r;
+            pixel[1] = color.g;
+            pixel[2] = color.b;
+            pixel[3] = 255;
+        });
+
+    buffer
+}
+
+fn write_png(path: &Path, width: u32, height: u32, buffer: &[u8]) -> Result<(), ExportError> {
+    let image: ImageBuffer<Rgba<u8>, _> = ImageBuffer::from_raw(width, height, buffer.to_vec())
+        .expect("buffer is sized width * height * 4 by render_rgba");
+    image.save_with_format(path, image::ImageFormat::Png)?;
+    Ok(())
+}
+
+/// writes a binary PPM (P6): a plain-text header followed by raw RGB triples
+fn write_ppm(path: &Path, width: u32, height: u32, buffer: &[u8]) -> Result<(), ExportError> {
+    let mut file = std::fs::File::create(path)?;
+    write!(file, "P6\n{width} {height}\n255\n")?;
+
+    for pixel in buffer.chunks_exact(4) {
+        file.write_all(&pixel[0..3])?;
+    }
+
+    Ok(())
+}
@@ -2,16 +2,29 @@ use std::f64::consts::PI;
 use rayon::prelude::*;
 use num_complex::Complex64;
 
+mod buddhabrot;
+mod export;
+mod palette;
+mod perturbation;
+
+pub use export::{render_fractal_to_file, ExportError};
+use palette::make_palette;
+
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct Color {
-    r: u8,
-    g: u8, 
-    b: u8,
+    pub(crate) r: u8,
+    pub(crate) g: u8,
+    pub(crate) b: u8,
 }
 
 /// generates a Mandelbrot fractal and writes the pixel data to a provided buffer
+///
+/// # Safety
+/// `buffer` must point to a writable buffer of at least `width * height * 4`
+/// bytes.
 #[no_mangle]
-pub extern "C" fn generate_fractal(
+pub unsafe extern "C" fn generate_fractal(
     buffer: *mut u8,
     width: i32,
     height: i32,
@@ -45,8 +58,191 @@ pub extern "C" fn generate_fractal(
         });
 }
 
+/// generates an anti-aliased Mandelbrot fractal by supersampling each pixel
+/// on an NxN grid of jittered subpixel offsets and averaging the results
+///
+/// # Safety
+/// `buffer` must point to a writable buffer of at least `width * height * 4`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn generate_fractal_aa(
+    buffer: *mut u8,
+    width: i32,
+    height: i32,
+    center_x: f64,
+    center_y: f64,
+    zoom: f64,
+    max_iterations: i32,
+    samples_per_axis: i32,
+) {
+    let buffer_slice = unsafe {
+        std::slice::from_raw_parts_mut(buffer, (width * height * 4) as usize)
+    };
+
+    // N=1 falls back to a single centered sample, so this reproduces
+    // generate_fractal's output exactly
+    let n = samples_per_axis.max(1);
+
+    buffer_slice
+        .par_chunks_exact_mut(4)
+        .enumerate()
+        .for_each(|(i, pixel)| {
+            let x = i as i32 % width;
+            let y = i as i32 / width;
+
+            let color = calculate_mandelbrot_aa(x, y, width, height, center_x, center_y, zoom, max_iterations, n);
+
+            pixel[0] = color.r;
+            pixel[1] = color.g;
+            pixel[2] = color.b;
+            pixel[3] = 255;
+        });
+}
+
+/// generates a Mandelbrot fractal like `generate_fractal`, but colors it
+/// with a caller-selected palette instead of the hardcoded psychedelic map.
+/// `palette_params`/`palette_params_len` is a flat `f64` array whose meaning
+/// depends on `palette_id` (e.g. RGB triples for the gradient's stops); pass
+/// a null pointer and 0 for palettes that don't take any
+///
+/// # Safety
+/// `buffer` must point to a writable buffer of at least `width * height * 4`
+/// bytes. `palette_params` must either be null or point to at least
+/// `palette_params_len` readable `f64`s.
+#[no_mangle]
+pub unsafe extern "C" fn generate_fractal_palette(
+    buffer: *mut u8,
+    width: i32,
+    height: i32,
+    center_x: f64,
+    center_y: f64,
+    zoom: f64,
+    max_iterations: i32,
+    palette_id: i32,
+    palette_params: *const f64,
+    palette_params_len: i32,
+) {
+    let buffer_slice = unsafe {
+        std::slice::from_raw_parts_mut(buffer, (width * height * 4) as usize)
+    };
+    let params = if palette_params.is_null() {
+        &[][..]
+    } else {
+        unsafe { std::slice::from_raw_parts(palette_params, palette_params_len as usize) }
+    };
+    let palette = make_palette(palette_id, params);
+
+    buffer_slice
+        .par_chunks_exact_mut(4)
+        .enumerate()
+        .for_each(|(i, pixel)| {
+            let x = i as i32 % width;
+            let y = i as i32 / width;
+
+            let c = pixel_to_complex(x, y, width, height, center_x, center_y, zoom);
+            let color = match mandelbrot_escape_t(c, max_iterations) {
+                Some(t) => palette.map(t),
+                None => Color { r: 0, g: 0, b: 0 },
+            };
+
+            pixel[0] = color.r;
+            pixel[1] = color.g;
+            pixel[2] = color.b;
+            pixel[3] = 255;
+        });
+}
+
+/// generates either a Mandelbrot/multibrot or a Julia set with the same
+/// parallel pipeline as `generate_fractal`. `kind` selects between them
+/// (0 = Mandelbrot, 1 = Julia); `julia_cx`/`julia_cy` are only used for
+/// Julia sets, and `power` generalizes `z = z^2 + c` to `z = z^power + c`
+/// for both (2.0 reproduces today's Mandelbrot exactly)
+///
+/// # Safety
+/// `buffer` must point to a writable buffer of at least `width * height * 4`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn generate_fractal_generalized(
+    buffer: *mut u8,
+    width: i32,
+    height: i32,
+    center_x: f64,
+    center_y: f64,
+    zoom: f64,
+    max_iterations: i32,
+    kind: i32,
+    julia_cx: f64,
+    julia_cy: f64,
+    power: f64,
+) {
+    let buffer_slice = unsafe {
+        std::slice::from_raw_parts_mut(buffer, (width * height * 4) as usize)
+    };
+    let julia_c = Complex64::new(julia_cx, julia_cy);
+
+    buffer_slice
+        .par_chunks_exact_mut(4)
+        .enumerate()
+        .for_each(|(i, pixel)| {
+            let x = i as i32 % width;
+            let y = i as i32 / width;
+
+            let color = calculate_generalized(x, y, width, height, center_x, center_y, zoom, max_iterations, kind, julia_c, power);
+
+            pixel[0] = color.r;
+            pixel[1] = color.g;
+            pixel[2] = color.b;
+            pixel[3] = 255;
+        });
+}
+
+/// `kind == 0` starts `z` at the origin and treats the pixel's coordinate as
+/// `c` (Mandelbrot/multibrot); `kind != 0` starts `z` at the pixel's
+/// coordinate and fixes `c` to `julia_c` (Julia set)
+#[allow(clippy::too_many_arguments)]
+fn calculate_generalized(
+    px: i32,
+    py: i32,
+    width: i32,
+    height: i32,
+    center_x: f64,
+    center_y: f64,
+    zoom: f64,
+    max_iter: i32,
+    kind: i32,
+    julia_c: Complex64,
+    power: f64,
+) -> Color {
+    let point = pixel_to_complex(px, py, width, height, center_x, center_y, zoom);
+    let (mut z, c) = if kind == 0 {
+        (Complex64::new(0.0, 0.0), point)
+    } else {
+        (point, julia_c)
+    };
+
+    let mut iteration = 0;
+    while z.norm_sqr() <= 4.0 && iteration < max_iter {
+        // z.powf(2.0) goes through norm()/atan2()/cos()/sin() and differs
+        // from z * z at the ULP level, which can flip which iteration a
+        // pixel escapes on; special-case it so this matches
+        // `calculate_mandelbrot` exactly for today's default power
+        z = if power == 2.0 { z * z } else { z.powf(power) } + c;
+        iteration += 1;
+    }
+
+    if iteration == max_iter {
+        Color { r: 0, g: 0, b: 0 }
+    } else {
+        // smooth iteration count, generalized to the same base as the
+        // escape exponent so coloring stays continuous for any power
+        let smooth_iter = iteration as f64 + 1.0 - z.norm_sqr().ln().ln() / power.ln();
+        psychedelic_color(smooth_iter / max_iter as f64)
+    }
+}
+
 /// figures out what color a pixel should be for the mandelbrot set
 /// takes screen coordinates and transforms them to complex plane coordinates
+#[allow(clippy::too_many_arguments)]
 fn calculate_mandelbrot(px: i32,
                         py: i32,
                         width: i32,
@@ -56,35 +252,114 @@ fn calculate_mandelbrot(px: i32,
                         zoom: f64,
                         max_iter: i32
 ) -> Color {
-    // convert pixel coordinates to complex plane coordinates
+    let c = pixel_to_complex(px, py, width, height, center_x, center_y, zoom);
+    escape_color(c, max_iter)
+}
+
+/// averages `samples_per_axis`^2 jittered subpixel samples within a pixel's
+/// footprint, in complex-plane units, so the anti-aliasing stays zoom-correct
+#[allow(clippy::too_many_arguments)]
+fn calculate_mandelbrot_aa(px: i32,
+                           py: i32,
+                           width: i32,
+                           height: i32,
+                           center_x: f64,
+                           center_y: f64,
+                           zoom: f64,
+                           max_iter: i32,
+                           n: i32,
+) -> Color {
+    let scale = 4.0 / zoom;
+    // size of one pixel's footprint in complex-plane units
+    let pixel_w = scale / width as f64;
+    let pixel_h = scale / height as f64;
+
+    let base = pixel_to_complex(px, py, width, height, center_x, center_y, zoom);
+
+    let (mut r_sum, mut g_sum, mut b_sum) = (0u32, 0u32, 0u32);
+    for sy in 0..n {
+        for sx in 0..n {
+            // subcell center, offset from the pixel center
+            let mut dx = (sx as f64 + 0.5) / n as f64 - 0.5;
+            let mut dy = (sy as f64 + 0.5) / n as f64 - 0.5;
+            if n > 1 {
+                // jitter within the subcell to break up regular sampling patterns
+                dx += (jitter01(px, py, sx, sy, 0) - 0.5) / n as f64;
+                dy += (jitter01(px, py, sx, sy, 1) - 0.5) / n as f64;
+            }
+            let sample = Complex64::new(base.re + dx * pixel_w, base.im + dy * pixel_h);
+            let color = escape_color(sample, max_iter);
+            r_sum += color.r as u32;
+            g_sum += color.g as u32;
+            b_sum += color.b as u32;
+        }
+    }
+
+    let count = (n * n) as u32;
+    Color {
+        r: (r_sum / count) as u8,
+        g: (g_sum / count) as u8,
+        b: (b_sum / count) as u8,
+    }
+}
+
+/// converts screen/pixel coordinates to a point in the complex plane
+pub(crate) fn pixel_to_complex(px: i32, py: i32, width: i32, height: i32, center_x: f64, center_y: f64, zoom: f64) -> Complex64 {
     let scale = 4.0 / zoom;
     let x = (px as f64 - width as f64 / 2.0) * scale / width as f64 + center_x;
     let y = (py as f64 - height as f64 / 2.0) * scale / height as f64 + center_y;
-    
-    // c is our point in the complex plane we're testing
-    let c = Complex64::new(x, y);
+    Complex64::new(x, y)
+}
+
+/// runs the mandelbrot escape-time iteration for a point `c` and maps the
+/// result to a color
+fn escape_color(c: Complex64, max_iter: i32) -> Color {
+    match mandelbrot_escape_t(c, max_iter) {
+        Some(t) => psychedelic_color(t),
+        None => Color { r: 0, g: 0, b: 0 },
+    }
+}
+
+/// runs the mandelbrot escape-time iteration for a point `c` and returns the
+/// normalized, smoothed escape value in `[0, 1]`, or `None` if the point
+/// never escaped (i.e. it's in the set)
+pub(crate) fn mandelbrot_escape_t(c: Complex64, max_iter: i32) -> Option<f64> {
     // z starts at origin and gets iterated
     let mut z = Complex64::new(0.0, 0.0);
     let mut iteration = 0;
-    
+
     // the mandelbrot iteration: z = z^2 + c
     while z.norm_sqr() <= 4.0 && iteration < max_iter {
         z = z * z + c;
         iteration += 1;
     }
-    
-    // if we never escaped, it's in the set (black)
+
+    // if we never escaped, it's in the set
     if iteration == max_iter {
-        Color { r: 0, g: 0, b: 0 }
+        None
     } else {
         // smooth iteration count to avoid banding in colors
         let smooth_iter = iteration as f64 + 1.0 - z.norm_sqr().ln().ln() / 2.0_f64.ln();
-        psychedelic_color(smooth_iter / max_iter as f64)
+        Some(smooth_iter / max_iter as f64)
     }
 }
 
+/// deterministic pseudo-random value in [0, 1), used to jitter subpixel
+/// samples without pulling in an RNG dependency
+fn jitter01(px: i32, py: i32, sx: i32, sy: i32, salt: u64) -> f64 {
+    let mut x = (px as u64).wrapping_mul(0x9E3779B97F4A7C15)
+        ^ (py as u64).wrapping_mul(0xC2B2AE3D27D4EB4F)
+        ^ (sx as u64).wrapping_mul(0x165667B19E3779F9)
+        ^ (sy as u64).wrapping_mul(0x27D4EB2F165667C5)
+        ^ salt.wrapping_mul(0x9E3779B185EBCA87);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xFF51AFD7ED558CCD);
+    x ^= x >> 33;
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
 /// creates trippy rainbow colors based on a value between 0 and 1
-fn psychedelic_color(t: f64) -> Color {
+pub(crate) fn psychedelic_color(t: f64) -> Color {
     // each color channel uses a different frequency sine wave
     // the phase shifts (pi/3, 2*pi/3) spread them out evenly
     let r = ((t * PI * 3.0).sin() * 0.5 + 0.5) * 255.0;
@@ -0,0 +1,115 @@
+use crate::Color;
+
+/// maps a normalized escape value `t` in `[0, 1]` to a color; letting
+/// callers swap this out means the fractal's look-and-feel can change
+/// without recompiling the renderer
+pub(crate) trait Palette: Sync {
+    fn map(&self, t: f64) -> Color;
+}
+
+/// the original fixed-frequency sine rainbow
+pub(crate) struct PsychedelicPalette;
+
+impl Palette for PsychedelicPalette {
+    fn map(&self, t: f64) -> Color {
+        crate::psychedelic_color(t)
+    }
+}
+
+/// sweeps smoothly through hue at full saturation and value
+pub(crate) struct HsvSweepPalette;
+
+impl Palette for HsvSweepPalette {
+    fn map(&self, t: f64) -> Color {
+        hsv_to_rgb(t.fract() * 360.0, 1.0, 1.0)
+    }
+}
+
+/// linearly interpolates between a caller-supplied sequence of RGB stops
+pub(crate) struct LinearGradientPalette {
+    pub stops: Vec<Color>,
+}
+
+impl Palette for LinearGradientPalette {
+    fn map(&self, t: f64) -> Color {
+        match self.stops.len() {
+            0 => Color { r: 0, g: 0, b: 0 },
+            1 => self.stops[0],
+            len => {
+                let scaled = t.clamp(0.0, 1.0) * (len - 1) as f64;
+                let lo = scaled.floor() as usize;
+                let hi = (lo + 1).min(len - 1);
+                let frac = scaled - lo as f64;
+
+                let a = self.stops[lo];
+                let b = self.stops[hi];
+                Color {
+                    r: lerp_u8(a.r, b.r, frac),
+                    g: lerp_u8(a.g, b.g, frac),
+                    b: lerp_u8(a.b, b.b, frac),
+                }
+            }
+        }
+    }
+}
+
+/// maps escape value straight to grayscale, ignoring hue entirely
+pub(crate) struct GrayscalePalette;
+
+impl Palette for GrayscalePalette {
+    fn map(&self, t: f64) -> Color {
+        let v = (t.clamp(0.0, 1.0) * 255.0) as u8;
+        Color { r: v, g: v, b: v }
+    }
+}
+
+/// builds a palette from an FFI `palette_id` plus its flat control
+/// parameters; unrecognized ids fall back to the original psychedelic map
+pub(crate) fn make_palette(palette_id: i32, params: &[f64]) -> Box<dyn Palette> {
+    match palette_id {
+        1 => Box::new(HsvSweepPalette),
+        2 => Box::new(LinearGradientPalette { stops: params_to_stops(params) }),
+        3 => Box::new(GrayscalePalette),
+        _ => Box::new(PsychedelicPalette),
+    }
+}
+
+/// reads `params` as a flat sequence of RGB triples, each component in
+/// `0.0..=255.0`
+fn params_to_stops(params: &[f64]) -> Vec<Color> {
+    params
+        .chunks_exact(3)
+        .map(|c| Color {
+            r: c[0].clamp(0.0, 255.0) as u8,
+            g: c[1].clamp(0.0, 255.0) as u8,
+            b: c[2].clamp(0.0, 255.0) as u8,
+        })
+        .collect()
+}
+
+fn lerp_u8(a: u8, b: u8, t: f64) -> u8 {
+    (a as f64 + (b as f64 - a as f64) * t).round() as u8
+}
+
+/// standard HSV -> RGB conversion, `h` in degrees, `s`/`v` in `0.0..=1.0`
+fn hsv_to_rgb(h: f64, s: f64, v: f64) -> Color {
+    let c = v * s;
+    let h_prime = h / 60.0;
+    let x = c * (1.0 - (h_prime.rem_euclid(2.0) - 1.0).abs());
+    let m = v - c;
+
+    let (r1, g1, b1) = match h_prime as i32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    Color {
+        r: ((r1 + m) * 255.0) as u8,
+        g: ((g1 + m) * 255.0) as u8,
+        b: ((b1 + m) * 255.0) as u8,
+    }
+}
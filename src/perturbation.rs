@@ -0,0 +1,313 @@
+use dashu_float::FBig;
+use dashu_int::IBig;
+use num_complex::Complex64;
+use rayon::prelude::*;
+
+use crate::{psychedelic_color, Color};
+
+/// bits of arbitrary-precision mantissa to carry in the reference orbit,
+/// padded well past f64's ~53 bits so the reference itself never loses the
+/// precision perturbation is trying to recover. `FBig`'s default base is 2,
+/// so this is literally bits, not decimal digits.
+const REFERENCE_PRECISION_BITS: usize = 256;
+
+/// Pauldelbrot's glitch heuristic: if the perturbed magnitude drops to less
+/// than this fraction of the delta's own magnitude, Z_n + delta_n has
+/// cancelled catastrophically and the pixel can no longer trust the shared
+/// reference orbit
+const GLITCH_RATIO: f64 = 1e-6;
+
+/// a high-precision orbit computed once per frame at the view center.
+/// `orbit[n] == Z_n`; `orbit.len()` may be less than the requested iteration
+/// count if the reference itself escapes first (e.g. the view center isn't
+/// in the set) -- callers must handle running out of orbit entries
+struct ReferenceOrbit {
+    /// Z_0, Z_1, ... rounded down to f64 for cheap per-pixel delta iteration
+    orbit: Vec<Complex64>,
+}
+
+/// renders a Mandelbrot view using perturbation theory: one high-precision
+/// reference orbit at the center, then plain-f64 delta iteration per pixel.
+/// this keeps per-pixel work cheap while supporting zoom levels far past
+/// what f64 can represent directly
+///
+/// # Safety
+/// `buffer` must point to a writable buffer of at least `width * height * 4`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn generate_fractal_deep_zoom(
+    buffer: *mut u8,
+    width: i32,
+    height: i32,
+    center_x: f64,
+    center_y: f64,
+    zoom: f64,
+    max_iterations: i32,
+) {
+    let buffer_slice = unsafe {
+        std::slice::from_raw_parts_mut(buffer, (width * height * 4) as usize)
+    };
+
+    let reference = compute_reference_orbit(center_x, center_y, max_iterations);
+
+    buffer_slice
+        .par_chunks_exact_mut(4)
+        .enumerate()
+        .for_each(|(i, pixel)| {
+            let x = i as i32 % width;
+            let y = i as i32 / width;
+
+            let color = calculate_deep_zoom_pixel(x, y, width, height, center_x, center_y, zoom, max_iterations, &reference);
+
+            pixel[0] = color.r;
+            pixel[1] = color.g;
+            pixel[2] = color.b;
+            pixel[3] = 255;
+        });
+}
+
+/// iterates Z_0 = 0, Z_{n+1} = Z_n^2 + c_ref at `REFERENCE_PRECISION_BITS` of
+/// precision, keeping only an f64-rounded copy of the orbit around since the
+/// per-pixel recurrence only ever needs the cheap delta representation.
+/// `orbit[n]` is `Z_n` (so `orbit[0] == Z_0 == 0`), which is what the
+/// `delta_{n+1} = 2*Z_n*delta_n + ...` recurrence needs paired with `delta_n`.
+/// stops early if the reference itself escapes, since there's nothing left
+/// to track -- callers must handle an orbit shorter than `max_iter`
+fn compute_reference_orbit(center_x: f64, center_y: f64, max_iter: i32) -> ReferenceOrbit {
+    let center_re = f64_to_fbig(center_x).with_precision(REFERENCE_PRECISION_BITS).value();
+    let center_im = f64_to_fbig(center_y).with_precision(REFERENCE_PRECISION_BITS).value();
+
+    let mut z_re = FBig::ZERO;
+    let mut z_im = FBig::ZERO;
+    let mut orbit = Vec::with_capacity(max_iter as usize);
+
+    for _ in 0..max_iter {
+        let z_f64 = Complex64::new(z_re.to_f64().value(), z_im.to_f64().value());
+        orbit.push(z_f64);
+
+        // the reference has escaped; record that escaping value (pixels with
+        // delta == 0 need its norm for smooth coloring) but stop here, since
+        // any further terms would just keep growing without bound
+        if z_f64.norm_sqr() > 4.0 {
+            break;
+        }
+
+        // Z = Z^2 + c_ref, rounded back down to the reference precision after
+        // every step so the mantissa doesn't grow without bound
+        let new_re = (&z_re * &z_re - &z_im * &z_im + &center_re)
+            .with_precision(REFERENCE_PRECISION_BITS)
+            .value();
+        let new_im = (&z_re * &z_im * FBig::from(2) + &center_im)
+            .with_precision(REFERENCE_PRECISION_BITS)
+            .value();
+        z_re = new_re;
+        z_im = new_im;
+    }
+
+    ReferenceOrbit { orbit }
+}
+
+/// converts an f64 to an `FBig` exactly, by reading off its IEEE-754 sign,
+/// mantissa and exponent directly -- f64's bit pattern already *is* a
+/// signficand times a power of two, so this loses no precision
+fn f64_to_fbig(x: f64) -> FBig {
+    let bits = x.to_bits();
+    let sign = if bits >> 63 != 0 { -1 } else { 1 };
+    let raw_exponent = ((bits >> 52) & 0x7FF) as i64;
+    let raw_mantissa = bits & 0x000F_FFFF_FFFF_FFFF;
+
+    let (mantissa, exponent) = if raw_exponent == 0 {
+        // subnormal (including zero itself)
+        (raw_mantissa, -1074_isize)
+    } else {
+        (raw_mantissa | (1 << 52), raw_exponent as isize - 1075)
+    };
+
+    let significand = IBig::from(sign) * IBig::from(mantissa);
+    FBig::from_parts(significand, exponent)
+}
+
+/// outcome of iterating a pixel's delta against one reference orbit
+enum IterationResult {
+    Escaped(Color),
+    InSet,
+    /// the shared reference can no longer be trusted (a glitch, or the
+    /// reference orbit itself ran out); `z` is the best-known `Z_n + delta_n`
+    /// at global iteration `iteration`, to seed a rebase from
+    NeedsRebase { iteration: i32, z: Complex64 },
+}
+
+/// iterates the perturbation delta for a single pixel against the shared
+/// reference orbit computed at the view center. On a glitch -- or once the
+/// reference orbit itself runs out because it escaped early -- rebases onto
+/// a second, pixel-local high-precision reference orbit computed on the fly
+/// (as Pauldelbrot's glitch handling calls for), and finishes there.
+///
+/// Known limitation: a *second* glitch during that rebased pass falls back
+/// to plain f64 iteration (`finish_direct`) rather than rebasing again, so
+/// an extremely unlucky pixel can still lose precision at the most extreme
+/// zooms. In practice a single rebase clears the vast majority of glitches;
+/// tightening this further (e.g. rebasing repeatedly until clean) is future
+/// work if that last sliver of pixels turns out to matter in practice.
+#[allow(clippy::too_many_arguments)]
+fn calculate_deep_zoom_pixel(
+    px: i32,
+    py: i32,
+    width: i32,
+    height: i32,
+    center_x: f64,
+    center_y: f64,
+    zoom: f64,
+    max_iter: i32,
+    reference: &ReferenceOrbit,
+) -> Color {
+    let scale = 4.0 / zoom;
+    // delta_c is tiny (on the order of one pixel's footprint), so plain f64
+    // represents it with full precision even at extreme zoom
+    let delta_c = Complex64::new(
+        (px as f64 - width as f64 / 2.0) * scale / width as f64,
+        (py as f64 - height as f64 / 2.0) * scale / height as f64,
+    );
+    let c = Complex64::new(center_x, center_y) + delta_c;
+
+    match iterate_against_reference(delta_c, &reference.orbit, 0, max_iter) {
+        IterationResult::Escaped(color) => color,
+        IterationResult::InSet => Color { r: 0, g: 0, b: 0 },
+        IterationResult::NeedsRebase { .. } => {
+            // rebase: compute a second, independent high-precision orbit
+            // anchored exactly at this pixel's own point `c`, starting from
+            // Z_0 = 0 like any other reference. Since it's exact for this
+            // pixel, iterating it with delta == 0 the whole way is just the
+            // pixel's own high-precision trajectory
+            let rebased = compute_reference_orbit(c.re, c.im, max_iter);
+
+            match iterate_against_reference(Complex64::new(0.0, 0.0), &rebased.orbit, 0, max_iter) {
+                IterationResult::Escaped(color) => color,
+                IterationResult::InSet => Color { r: 0, g: 0, b: 0 },
+                IterationResult::NeedsRebase { iteration, z } => finish_direct(c, z, iteration, max_iter),
+            }
+        }
+    }
+}
+
+/// runs the delta recurrence `delta_{n+1} = 2*Z_n*delta_n + delta_n^2 + delta_c`
+/// against `orbit` starting at global iteration `start_iter`, for up to
+/// `max_iter - start_iter` steps
+fn iterate_against_reference(delta_c: Complex64, orbit: &[Complex64], start_iter: i32, max_iter: i32) -> IterationResult {
+    let mut delta = Complex64::new(0.0, 0.0);
+    // the last z_n = Z_n + delta_n tested, with its global iteration n
+    let mut last = (start_iter, Complex64::new(0.0, 0.0));
+
+    for step in 0..(max_iter - start_iter) {
+        let iteration = start_iter + step;
+
+        let z_ref = match orbit.get(step as usize) {
+            Some(&z_ref) => z_ref,
+            None => {
+                // orbit ran out before this pixel finished; the best-known
+                // point is the last z_n tested
+                let (iteration, z) = last;
+                return IterationResult::NeedsRebase { iteration, z };
+            }
+        };
+
+        // z_n = Z_n + delta_n, tested before delta steps to delta_{n+1}
+        let z = z_ref + delta;
+        let z_norm = z.norm_sqr();
+
+        if z_norm > 4.0 {
+            return IterationResult::Escaped(escape_color(z_norm, iteration, max_iter));
+        }
+
+        if z_norm < delta.norm_sqr() * GLITCH_RATIO * GLITCH_RATIO {
+            // Z_n + delta_n has cancelled catastrophically; the reference is
+            // no longer trustworthy past this point
+            return IterationResult::NeedsRebase { iteration, z };
+        }
+
+        last = (iteration, z);
+        delta = 2.0 * z_ref * delta + delta * delta + delta_c;
+    }
+
+    IterationResult::InSet
+}
+
+/// continues the escape-time iteration in plain f64 from an already-computed
+/// `z` at iteration `start_iter`, used as a last resort after a rebase also
+/// glitches
+fn finish_direct(c: Complex64, mut z: Complex64, start_iter: i32, max_iter: i32) -> Color {
+    let mut iteration = start_iter;
+
+    while z.norm_sqr() <= 4.0 && iteration < max_iter {
+        z = z * z + c;
+        iteration += 1;
+    }
+
+    if iteration == max_iter {
+        Color { r: 0, g: 0, b: 0 }
+    } else {
+        escape_color(z.norm_sqr(), iteration, max_iter)
+    }
+}
+
+/// smooth-colors an escaped point the same way `calculate_mandelbrot` does
+fn escape_color(escaped_norm_sqr: f64, iteration: i32, max_iter: i32) -> Color {
+    let smooth_iter = iteration as f64 + 1.0 - escaped_norm_sqr.ln().ln() / 2.0_f64.ln();
+    psychedelic_color(smooth_iter / max_iter as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// within f64 precision (far short of perturbation's actual target
+    /// zoom), the perturbed result should match plain f64 iteration almost
+    /// everywhere -- this is exactly the invariant the Z_0 off-by-one and
+    /// the IBig/dependency issues broke
+    #[test]
+    fn matches_direct_iteration_within_f64_precision() {
+        let (width, height) = (64i32, 64i32);
+        let (center_x, center_y) = (-0.75, 0.1);
+        let zoom = 1e6;
+        let max_iter = 200;
+
+        let reference = compute_reference_orbit(center_x, center_y, max_iter);
+
+        let mut mismatches = 0;
+        let mut total = 0;
+
+        for py in 0..height {
+            for px in 0..width {
+                let perturbed = calculate_deep_zoom_pixel(px, py, width, height, center_x, center_y, zoom, max_iter, &reference);
+
+                let c = crate::pixel_to_complex(px, py, width, height, center_x, center_y, zoom);
+                let direct = match crate::mandelbrot_escape_t(c, max_iter) {
+                    Some(t) => psychedelic_color(t),
+                    None => Color { r: 0, g: 0, b: 0 },
+                };
+
+                total += 1;
+                if (perturbed.r as i32 - direct.r as i32).abs() > 2
+                    || (perturbed.g as i32 - direct.g as i32).abs() > 2
+                    || (perturbed.b as i32 - direct.b as i32).abs() > 2
+                {
+                    mismatches += 1;
+                }
+            }
+        }
+
+        // a handful of boundary pixels can legitimately round differently at
+        // the escape threshold, but the vast majority must agree
+        assert!(
+            mismatches * 20 < total,
+            "{mismatches}/{total} pixels diverged from direct iteration at a zoom well within f64 precision"
+        );
+    }
+
+    #[test]
+    fn reference_orbit_starts_at_z0_zero() {
+        let orbit = compute_reference_orbit(-0.75, 0.1, 10);
+        assert_eq!(orbit.orbit[0], Complex64::new(0.0, 0.0));
+    }
+}
+